@@ -1,6 +1,10 @@
 use proc_macro2::{Span, TokenStream};
 use quote::{format_ident, quote};
-use syn::{parse_quote, Data, DeriveInput, Fields, Ident, Path};
+use syn::punctuated::Punctuated;
+use syn::{
+    parse_quote, Attribute, Data, DataEnum, DeriveInput, Expr, ExprLit, ExprUnary, Fields, Ident,
+    Lit, NestedMeta, Path, Token, UnOp, Visibility,
+};
 
 pub struct EmitOptions {
     /// The Rust-friendly name of the layout, like Std140.
@@ -62,6 +66,124 @@ impl EmitOptions {
     }
 
     pub fn emit(&self, input: DeriveInput) -> TokenStream {
+        let name = input.ident.clone();
+        let visibility = input.vis.clone();
+        let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+        match &input.data {
+            Data::Struct(data) => match &data.fields {
+                Fields::Named(fields) => self.emit_struct(
+                    name,
+                    visibility,
+                    &impl_generics,
+                    &ty_generics,
+                    &where_clause,
+                    fields,
+                    &input.attrs,
+                ),
+                Fields::Unnamed(_) => panic!("Tuple structs are not supported"),
+                Fields::Unit => panic!("Unit structs are not supported"),
+            },
+            Data::Enum(data) => self.emit_enum(
+                name,
+                &impl_generics,
+                &ty_generics,
+                &where_clause,
+                data,
+                &input.attrs,
+            ),
+            Data::Union(_) => panic!("Only structs and fieldless enums are supported"),
+        }
+    }
+
+    /// Emits the As* trait impl for a fieldless (C-like) enum, representing it
+    /// as its 32-bit discriminant (`u32` or `i32` is used as the layout type;
+    /// no new layout type is generated), since GLSL/WGSL only have 32-bit
+    /// integer types.
+    fn emit_enum(
+        &self,
+        name: Ident,
+        impl_generics: &syn::ImplGenerics,
+        ty_generics: &syn::TypeGenerics,
+        where_clause: &Option<&syn::WhereClause>,
+        data: &DataEnum,
+        attrs: &[Attribute],
+    ) -> TokenStream {
+        let as_trait_path = &self.as_trait_path;
+        let as_trait_assoc = &self.as_trait_assoc;
+        let as_trait_method = &self.as_trait_method;
+        let from_trait_method = &self.from_trait_method;
+
+        for variant in &data.variants {
+            if !matches!(variant.fields, Fields::Unit) {
+                panic!("Only fieldless enums are supported");
+            }
+        }
+
+        let discriminants = enum_discriminants(data);
+        let min = discriminants.iter().copied().min().unwrap_or(0);
+        let max = discriminants.iter().copied().max().unwrap_or(0);
+
+        let int_ty = match explicit_int_repr(attrs) {
+            Some(int_ty) => {
+                check_discriminants_fit(&int_ty, min, max);
+                int_ty
+            }
+            None => default_int_repr(min, max),
+        };
+
+        let variant_idents: Vec<_> = data.variants.iter().map(|variant| &variant.ident).collect();
+        let discriminant_literals: Vec<_> = discriminants
+            .iter()
+            .map(|value| {
+                let value = *value as i64;
+                if int_ty == "u32" {
+                    let value = value as u32;
+                    quote!(#value)
+                } else {
+                    let value = value as i32;
+                    quote!(#value)
+                }
+            })
+            .collect();
+
+        let int_ty = Ident::new(&int_ty, Span::call_site());
+
+        quote! {
+            impl #impl_generics #as_trait_path for #name #ty_generics #where_clause {
+                type #as_trait_assoc = #int_ty;
+
+                fn #as_trait_method(&self) -> Self::#as_trait_assoc {
+                    match self {
+                        #( Self::#variant_idents => #discriminant_literals, )*
+                    }
+                }
+
+                fn #from_trait_method(value: Self::#as_trait_assoc) -> Self {
+                    match value {
+                        #( #discriminant_literals => Self::#variant_idents, )*
+                        other => panic!(
+                            "{} is not a valid discriminant for enum {}",
+                            other,
+                            stringify!(#name),
+                        ),
+                    }
+                }
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn emit_struct(
+        &self,
+        name: Ident,
+        visibility: Visibility,
+        impl_generics: &syn::ImplGenerics,
+        ty_generics: &syn::TypeGenerics,
+        where_clause: &Option<&syn::WhereClause>,
+        fields: &syn::FieldsNamed,
+        attrs: &[Attribute],
+    ) -> TokenStream {
         let min_struct_alignment = self.min_struct_alignment;
         let layout_name = &self.layout_name;
         let mod_path = &self.mod_path;
@@ -71,27 +193,38 @@ impl EmitOptions {
         let as_trait_method = &self.as_trait_method;
         let from_trait_method = &self.from_trait_method;
 
-        let visibility = input.vis;
-
-        let name = input.ident;
         let generated_name = format_ident!("{}{}", layout_name, name);
 
-        let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+        // A field marked `#[crevice(runtime_sized)]` must be the last field,
+        // and turns into a dynamically sized tail appended after the header
+        // generated from every other field, rather than a field of the
+        // generated struct itself.
+        let runtime_sized_index = fields
+            .named
+            .iter()
+            .position(has_runtime_sized_attr);
 
-        let fields = match &input.data {
-            Data::Struct(data) => match &data.fields {
-                Fields::Named(fields) => fields,
-                Fields::Unnamed(_) => panic!("Tuple structs are not supported"),
-                Fields::Unit => panic!("Unit structs are not supported"),
-            },
-            Data::Enum(_) | Data::Union(_) => panic!("Only structs are supported"),
-        };
+        if let Some(index) = runtime_sized_index {
+            if index != fields.named.len() - 1 {
+                panic!("#[crevice(runtime_sized)] is only supported on a struct's last field");
+            }
+
+            if *layout_name != "Std430" {
+                panic!("#[crevice(runtime_sized)] is only supported when deriving Std430");
+            }
+        }
+
+        let head_fields: Vec<_> = fields
+            .named
+            .iter()
+            .take(runtime_sized_index.unwrap_or(fields.named.len()))
+            .collect();
+        let tail_field = runtime_sized_index.map(|index| &fields.named[index]);
 
         // Generate the names we'll use for calculating alignment of each field.
         // Each name will turn into a const fn that's invoked to compute the
         // size of a padding array before each field.
-        let align_names: Vec<_> = fields
-            .named
+        let align_names: Vec<_> = head_fields
             .iter()
             .map(|field| {
                 format_ident!(
@@ -107,31 +240,28 @@ impl EmitOptions {
         // padding. Each function invokes all previous functions to calculate
         // the total offset into the struct for the current field, then aligns
         // up to the nearest multiple of alignment.
-        let alignment_calculators: Vec<_> = fields
-            .named
+        let alignment_calculators: Vec<_> = head_fields
             .iter()
             .enumerate()
             .map(|(index, field)| {
                 let align_name = &align_names[index];
 
-                let offset_accumulation =
-                    fields
-                        .named
-                        .iter()
-                        .zip(&align_names)
-                        .take(index)
-                        .map(|(field, align_name)| {
-                            let field_ty = &field.ty;
-                            quote! {
-                                offset += #align_name();
-                                offset += ::core::mem::size_of::<<#field_ty as #as_trait_path>::#as_trait_assoc>();
-                            }
-                        });
+                let offset_accumulation = head_fields
+                    .iter()
+                    .zip(&align_names)
+                    .take(index)
+                    .map(|(field, align_name)| {
+                        let field_ty = &field.ty;
+                        quote! {
+                            offset += #align_name();
+                            offset += ::core::mem::size_of::<<#field_ty as #as_trait_path>::#as_trait_assoc>();
+                        }
+                    });
 
                 let pad_at_end = index
                     .checked_sub(1)
                     .map_or(quote!{0usize}, |prev_index|{
-                        let field = &fields.named[prev_index];
+                        let field = &head_fields[prev_index];
                         let field_ty = &field.ty;
                         quote! {
                             if <<#field_ty as #as_trait_path>::#as_trait_assoc as #mod_path::#layout_name>::PAD_AT_END {
@@ -145,7 +275,34 @@ impl EmitOptions {
 
                 let field_ty = &field.ty;
 
+                // A `#[crevice(align = N)]` override raises the alignment
+                // used for the leading padding array, and is rejected at
+                // compile time if it's smaller than the field's natural
+                // alignment (RFC 1358's `#[repr(align(N))]` works the same
+                // way).
+                let explicit_align = explicit_align_attr(field).map(|align| {
+                    quote! {
+                        const _: () = assert!(
+                            #align >= <<#field_ty as #as_trait_path>::#as_trait_assoc as #mod_path::#layout_name>::ALIGNMENT,
+                            "#[crevice(align = ...)] must be at least the field's natural alignment",
+                        );
+                    }
+                });
+
+                let explicit_align_value = explicit_align_attr(field)
+                    .map(|align| quote!(#align))
+                    .unwrap_or(quote!(0usize));
+
+                let alignment = quote! {
+                    ::crevice::internal::max(
+                        <<#field_ty as #as_trait_path>::#as_trait_assoc as #mod_path::#layout_name>::ALIGNMENT,
+                        #explicit_align_value
+                    )
+                };
+
                 quote! {
+                    #explicit_align
+
                     #[allow(non_snake_case)]
                     pub const fn #align_name() -> usize {
                         let mut offset = 0;
@@ -154,7 +311,7 @@ impl EmitOptions {
                         ::crevice::internal::align_offset(
                             offset,
                             ::crevice::internal::max(
-                                <<#field_ty as #as_trait_path>::#as_trait_assoc as #mod_path::#layout_name>::ALIGNMENT,
+                                #alignment,
                                 #pad_at_end
                             )
                         )
@@ -169,8 +326,7 @@ impl EmitOptions {
         //
         // * Alignment, a byte array whose size is computed from #align_name().
         // * Data, the layout-specific version of the original field.
-        let generated_fields: Vec<_> = fields
-            .named
+        let generated_fields: Vec<_> = head_fields
             .iter()
             .zip(&align_names)
             .map(|(field, align_name)| {
@@ -184,11 +340,102 @@ impl EmitOptions {
             })
             .collect();
 
+        // Generate an expression computing each field's absolute byte offset
+        // within the generated struct, reusing the same offset-accumulation
+        // used by the #align_name() const fns above.
+        let field_offsets: Vec<_> = head_fields
+            .iter()
+            .enumerate()
+            .map(|(index, _)| {
+                let offset_accumulation = head_fields
+                    .iter()
+                    .zip(&align_names)
+                    .take(index)
+                    .map(|(field, align_name)| {
+                        let field_ty = &field.ty;
+                        quote! {
+                            offset += #align_name();
+                            offset += ::core::mem::size_of::<<#field_ty as #as_trait_path>::#as_trait_assoc>();
+                        }
+                    });
+                let align_name = &align_names[index];
+
+                quote! {
+                    {
+                        let mut offset = 0;
+                        #( #offset_accumulation )*
+                        offset += #align_name();
+                        offset
+                    }
+                }
+            })
+            .collect();
+
+        // Generate the `FIELDS` introspection metadata, exposing each
+        // field's offset/size/alignment so callers can cross-check the
+        // generated layout against shader-reflection tooling.
+        let field_layout_entries: Vec<_> = head_fields
+            .iter()
+            .zip(&field_offsets)
+            .map(|(field, offset)| {
+                let field_name = field.ident.as_ref().unwrap();
+                let field_name_str = field_name.to_string();
+                let field_ty = &field.ty;
+
+                let alignment = {
+                    let natural = quote! {
+                        <<#field_ty as #as_trait_path>::#as_trait_assoc as #mod_path::#layout_name>::ALIGNMENT
+                    };
+                    match explicit_align_attr(field) {
+                        Some(align) => quote!(::crevice::internal::max(#natural, #align)),
+                        None => natural,
+                    }
+                };
+
+                quote! {
+                    ::crevice::internal::FieldLayout {
+                        name: #field_name_str,
+                        offset: #offset,
+                        size: ::core::mem::size_of::<<#field_ty as #as_trait_path>::#as_trait_assoc>(),
+                        alignment: #alignment,
+                    }
+                }
+            })
+            .collect();
+
+        // An optional `#[crevice(validate(field = offset, ..))]` on the
+        // struct turns the expected offset of each named field into a
+        // compile-time assertion, so a layout drift against an external
+        // shader-reflection description becomes a compile error instead of
+        // a runtime bug.
+        let validation_assertions: Vec<_> = validate_struct_attr(attrs)
+            .into_iter()
+            .map(|(field_name, expected_offset)| {
+                let index = head_fields
+                    .iter()
+                    .position(|field| *field.ident.as_ref().unwrap() == field_name)
+                    .unwrap_or_else(|| {
+                        panic!(
+                            "#[crevice(validate(..))] references unknown field `{}`",
+                            field_name
+                        )
+                    });
+                let offset = &field_offsets[index];
+                let message = format!(
+                    "field `{}` does not match the offset given in #[crevice(validate(..))]",
+                    field_name
+                );
+
+                quote! {
+                    const _: () = assert!(#offset == #expected_offset, #message);
+                }
+            })
+            .collect();
+
         // Generate an initializer for each field in the original struct.
         // Alignment fields are filled in with zeroes using struct update
         // syntax.
-        let field_initializers: Vec<_> = fields
-            .named
+        let field_initializers: Vec<_> = head_fields
             .iter()
             .map(|field| {
                 let field_name = field.ident.as_ref().unwrap();
@@ -197,8 +444,7 @@ impl EmitOptions {
             })
             .collect();
 
-        let field_unwrappers: Vec<_> = fields
-            .named
+        let field_unwrappers: Vec<_> = head_fields
             .iter()
             .map(|field|{
                 let field_name = field.ident.as_ref().unwrap();
@@ -215,14 +461,20 @@ impl EmitOptions {
         // ...we should generate an expression like this:
         //
         // max(ty2_align, max(ty1_align, min_align))
-        let struct_alignment = fields.named.iter().fold(
+        let struct_alignment = head_fields.iter().fold(
             quote!(#min_struct_alignment),
             |last, field| {
                 let field_ty = &field.ty;
+                let explicit_align_value = explicit_align_attr(field)
+                    .map(|align| quote!(#align))
+                    .unwrap_or(quote!(0usize));
 
                 quote! {
                     ::crevice::internal::max(
-                        <<#field_ty as #as_trait_path>::#as_trait_assoc as #trait_path>::ALIGNMENT,
+                        ::crevice::internal::max(
+                            <<#field_ty as #as_trait_path>::#as_trait_assoc as #trait_path>::ALIGNMENT,
+                            #explicit_align_value,
+                        ),
                         #last,
                     )
                 }
@@ -237,7 +489,7 @@ impl EmitOptions {
             quote!()
         };
 
-        quote! {
+        let header = quote! {
             #( #alignment_calculators )*
 
             #[derive(Debug, Clone, Copy)]
@@ -256,23 +508,479 @@ impl EmitOptions {
                 const PAD_AT_END: bool = true;
             }
 
-            impl #impl_generics #as_trait_path for #name #ty_generics #where_clause {
-                type #as_trait_assoc = #generated_name;
+            impl #impl_generics #generated_name #ty_generics #where_clause {
+                /// Per-field offset/size/alignment metadata, in declaration
+                /// order. Useful for cross-checking this layout against
+                /// shader-reflection output.
+                pub const FIELDS: &'static [::crevice::internal::FieldLayout] = &[
+                    #( #field_layout_entries, )*
+                ];
+            }
 
-                fn #as_trait_method(&self) -> Self::#as_trait_assoc {
-                    Self::#as_trait_assoc {
-                        #( #field_initializers, )*
+            #( #validation_assertions )*
+        };
+
+        match tail_field {
+            None => quote! {
+                #header
+
+                impl #impl_generics #as_trait_path for #name #ty_generics #where_clause {
+                    type #as_trait_assoc = #generated_name;
+
+                    fn #as_trait_method(&self) -> Self::#as_trait_assoc {
+                        Self::#as_trait_assoc {
+                            #( #field_initializers, )*
 
-                        ..::crevice::internal::bytemuck::Zeroable::zeroed()
+                            ..::crevice::internal::bytemuck::Zeroable::zeroed()
+                        }
+                    }
+
+                    fn #from_trait_method(value: Self::#as_trait_assoc) -> Self {
+                        Self {
+                            #( #field_unwrappers, )*
+                        }
                     }
                 }
+            },
+            Some(tail_field) => {
+                let elem_ty = runtime_sized_elem_ty(&tail_field.ty);
+                let tail_name = tail_field.ident.as_ref().unwrap();
+
+                // Pretend there's one more field after the header, of the
+                // tail's element type, to compute the offset at which the
+                // dynamically sized array begins.
+                let tail_offset_accumulation = head_fields.iter().zip(&align_names).map(
+                    |(field, align_name)| {
+                        let field_ty = &field.ty;
+                        quote! {
+                            offset += #align_name();
+                            offset += ::core::mem::size_of::<<#field_ty as #as_trait_path>::#as_trait_assoc>();
+                        }
+                    },
+                );
+
+                let tail_pad_at_end = head_fields.last().map_or(quote!(0usize), |field| {
+                    let field_ty = &field.ty;
+                    quote! {
+                        if <<#field_ty as #as_trait_path>::#as_trait_assoc as #mod_path::#layout_name>::PAD_AT_END {
+                            <<#field_ty as #as_trait_path>::#as_trait_assoc as #mod_path::#layout_name>::ALIGNMENT
+                        }
+                        else {
+                            0usize
+                        }
+                    }
+                });
 
-                fn #from_trait_method(value: Self::#as_trait_assoc) -> Self {
-                    Self {
-                        #( #field_unwrappers, )*
+                let tail_align_name =
+                    format_ident!("_{}__{}__{}__align", name, tail_name, layout_name);
+
+                quote! {
+                    #header
+
+                    /// Absolute byte offset at which the runtime-sized tail
+                    /// begins, i.e. the end of the header's last field
+                    /// rounded up to the tail element's alignment, or to
+                    /// that last field's alignment if it pads at its end,
+                    /// whichever is larger.
+                    #[allow(non_snake_case)]
+                    pub const fn #tail_align_name() -> usize {
+                        let mut offset = 0;
+                        #( #tail_offset_accumulation )*
+
+                        offset += ::crevice::internal::align_offset(
+                            offset,
+                            ::crevice::internal::max(
+                                <<#elem_ty as #as_trait_path>::#as_trait_assoc as #mod_path::#layout_name>::ALIGNMENT,
+                                #tail_pad_at_end
+                            )
+                        );
+
+                        offset
+                    }
+
+                    impl #impl_generics #name #ty_generics #where_clause {
+                        /// Returns the number of bytes needed to hold this
+                        /// struct's header followed by `len` tightly strided
+                        /// elements of its runtime-sized tail array.
+                        pub fn std430_size(len: usize) -> usize {
+                            let elem_size = ::core::mem::size_of::<<#elem_ty as #as_trait_path>::#as_trait_assoc>();
+                            let stride = elem_size + ::crevice::internal::align_offset(
+                                elem_size,
+                                ::crevice::internal::max(
+                                    <<#elem_ty as #as_trait_path>::#as_trait_assoc as #mod_path::#layout_name>::ALIGNMENT,
+                                    #min_struct_alignment,
+                                ),
+                            );
+
+                            let total = #tail_align_name() + stride * len;
+                            total + ::crevice::internal::align_offset(
+                                total,
+                                <#generated_name #ty_generics as #mod_path::#layout_name>::ALIGNMENT,
+                            )
+                        }
+
+                        /// Writes this struct's header followed by its
+                        /// runtime-sized tail array into `dst`, which must be
+                        /// at least `Self::std430_size(self.#tail_name.len())`
+                        /// bytes long.
+                        pub fn write_std430(&self, dst: &mut [u8]) {
+                            let header = #generated_name {
+                                #( #field_initializers, )*
+
+                                ..::crevice::internal::bytemuck::Zeroable::zeroed()
+                            };
+
+                            let header_bytes = ::crevice::internal::bytemuck::bytes_of(&header);
+                            dst[..header_bytes.len()].copy_from_slice(header_bytes);
+
+                            let elem_size = ::core::mem::size_of::<<#elem_ty as #as_trait_path>::#as_trait_assoc>();
+                            let stride = elem_size + ::crevice::internal::align_offset(
+                                elem_size,
+                                ::crevice::internal::max(
+                                    <<#elem_ty as #as_trait_path>::#as_trait_assoc as #mod_path::#layout_name>::ALIGNMENT,
+                                    #min_struct_alignment,
+                                ),
+                            );
+
+                            let tail_start = #tail_align_name();
+                            // The header + its own padding may end before the
+                            // tail's larger alignment requirement, e.g. a
+                            // `u32`-terminated header followed by 16-byte
+                            // tail elements; zero that gap rather than
+                            // leaving it as stale bytes from a reused `dst`.
+                            for byte in &mut dst[header_bytes.len()..tail_start] {
+                                *byte = 0;
+                            }
+
+                            let mut offset = tail_start;
+                            for item in self.#tail_name.iter() {
+                                let value = item.#as_trait_method();
+                                let bytes = ::crevice::internal::bytemuck::bytes_of(&value);
+                                dst[offset..offset + bytes.len()].copy_from_slice(bytes);
+                                offset += stride;
+                            }
+                        }
                     }
                 }
             }
         }
     }
 }
+
+/// Parses the contents of every `#[crevice(..)]` attribute in `attrs` (of a
+/// field or of a struct) into its individual comma-separated items, e.g.
+/// `runtime_sized`, `align = 16` or `validate(a = 0)`.
+fn crevice_meta_items(attrs: &[Attribute]) -> Vec<syn::Meta> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("crevice"))
+        .flat_map(|attr| {
+            attr.parse_args_with(Punctuated::<syn::Meta, Token![,]>::parse_terminated)
+                .expect("malformed #[crevice(..)] attribute")
+        })
+        .collect()
+}
+
+/// Returns true if a field is annotated with `#[crevice(runtime_sized)]`.
+fn has_runtime_sized_attr(field: &syn::Field) -> bool {
+    crevice_meta_items(&field.attrs)
+        .iter()
+        .any(|meta| matches!(meta, syn::Meta::Path(path) if path.is_ident("runtime_sized")))
+}
+
+/// Returns the override from a field's `#[crevice(align = N)]` attribute, if
+/// present. `N` must be a power of two; it is not checked here against the
+/// field's natural alignment, since that isn't known until the field's type
+/// is resolved; instead the generated code asserts it at compile time.
+fn explicit_align_attr(field: &syn::Field) -> Option<usize> {
+    crevice_meta_items(&field.attrs)
+        .into_iter()
+        .find_map(|meta| {
+            let name_value = match meta {
+                syn::Meta::NameValue(name_value) if name_value.path.is_ident("align") => name_value,
+                _ => return None,
+            };
+
+            let align = match name_value.lit {
+                Lit::Int(lit) => lit
+                    .base10_parse::<usize>()
+                    .expect("#[crevice(align = N)] value is out of range"),
+                _ => panic!("#[crevice(align = N)] expects an integer"),
+            };
+
+            if !align.is_power_of_two() {
+                panic!("#[crevice(align = N)] requires N to be a power of two");
+            }
+
+            Some(align)
+        })
+}
+
+/// Parses a struct-level `#[crevice(validate(field = offset, ..))]`
+/// attribute into the list of `(field name, expected offset)` pairs it
+/// names.
+fn validate_struct_attr(attrs: &[Attribute]) -> Vec<(String, usize)> {
+    crevice_meta_items(attrs)
+        .into_iter()
+        .find_map(|meta| match meta {
+            syn::Meta::List(list) if list.path.is_ident("validate") => Some(list.nested),
+            _ => None,
+        })
+        .into_iter()
+        .flatten()
+        .map(|nested| match nested {
+            NestedMeta::Meta(syn::Meta::NameValue(name_value)) => {
+                let field_name = name_value
+                    .path
+                    .get_ident()
+                    .expect("#[crevice(validate(..))] entries must be `field = offset`")
+                    .to_string();
+
+                let offset = match name_value.lit {
+                    Lit::Int(lit) => lit
+                        .base10_parse::<usize>()
+                        .expect("#[crevice(validate(..))] offset is out of range"),
+                    _ => panic!("#[crevice(validate(..))] offsets must be integers"),
+                };
+
+                (field_name, offset)
+            }
+            _ => panic!("#[crevice(validate(..))] entries must be `field = offset`"),
+        })
+        .collect()
+}
+
+/// Extracts the element type `T` out of a `#[crevice(runtime_sized)]` field
+/// typed as `Vec<T>` or `[T]`.
+fn runtime_sized_elem_ty(ty: &syn::Type) -> syn::Type {
+    if let syn::Type::Slice(slice) = ty {
+        return (*slice.elem).clone();
+    }
+
+    if let syn::Type::Path(path) = ty {
+        if let Some(segment) = path.path.segments.last() {
+            if segment.ident == "Vec" {
+                if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+                    if let Some(syn::GenericArgument::Type(elem_ty)) = args.args.first() {
+                        return elem_ty.clone();
+                    }
+                }
+            }
+        }
+    }
+
+    panic!("#[crevice(runtime_sized)] fields must have type Vec<T> or [T]");
+}
+
+/// Computes the discriminant of each variant in a fieldless enum, following
+/// the normal Rust rule that an unspecified discriminant is one greater than
+/// the previous variant's (starting at 0).
+fn enum_discriminants(data: &DataEnum) -> Vec<i128> {
+    let mut next = 0i128;
+
+    data.variants
+        .iter()
+        .map(|variant| {
+            let value = match &variant.discriminant {
+                Some((_, expr)) => eval_discriminant(expr),
+                None => next,
+            };
+            next = value + 1;
+            value
+        })
+        .collect()
+}
+
+fn eval_discriminant(expr: &Expr) -> i128 {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(lit), ..
+        }) => lit
+            .base10_parse()
+            .expect("enum discriminant is out of range"),
+        Expr::Unary(ExprUnary {
+            op: UnOp::Neg(_),
+            expr,
+            ..
+        }) => -eval_discriminant(expr),
+        _ => panic!("enum discriminants must be literal integers"),
+    }
+}
+
+/// Looks for an explicit `#[repr(u32)]` or `#[repr(i32)]` on the enum, which
+/// overrides the discriminant type we would otherwise infer. Other `repr`
+/// modifiers, like `#[repr(u32, align(4))]`'s `align(4)`, are ignored rather
+/// than rejected.
+fn explicit_int_repr(attrs: &[Attribute]) -> Option<String> {
+    for attr in attrs {
+        if !attr.path.is_ident("repr") {
+            continue;
+        }
+
+        let nested = attr
+            .parse_args_with(Punctuated::<NestedMeta, Token![,]>::parse_terminated)
+            .expect("malformed #[repr(..)] attribute");
+
+        for meta in nested {
+            let path = match &meta {
+                NestedMeta::Meta(syn::Meta::Path(path)) => path,
+                _ => continue,
+            };
+
+            if path.is_ident("u32") {
+                return Some("u32".to_string());
+            } else if path.is_ident("i32") {
+                return Some("i32".to_string());
+            }
+        }
+    }
+
+    None
+}
+
+/// Mirrors rustc's `Integer::repr_discr`: pick the smallest of `u32`/`i32`
+/// that covers every discriminant, preferring unsigned when possible.
+fn default_int_repr(min: i128, max: i128) -> String {
+    if min >= 0 {
+        if max <= u32::MAX as i128 {
+            return "u32".to_string();
+        }
+    } else if min >= i32::MIN as i128 && max <= i32::MAX as i128 {
+        return "i32".to_string();
+    }
+
+    panic!(
+        "enum discriminants do not fit in a 32-bit integer, which is required for shader layouts"
+    );
+}
+
+fn check_discriminants_fit(int_ty: &str, min: i128, max: i128) {
+    let (lo, hi) = if int_ty == "u32" {
+        (0i128, u32::MAX as i128)
+    } else {
+        (i32::MIN as i128, i32::MAX as i128)
+    };
+
+    if min < lo || max > hi {
+        panic!(
+            "enum discriminants do not fit in the explicit #[repr({})]",
+            int_ty
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse::Parser;
+
+    #[test]
+    fn explicit_int_repr_simple() {
+        let attrs: Vec<Attribute> = vec![parse_quote!(#[repr(u32)])];
+        assert_eq!(explicit_int_repr(&attrs), Some("u32".to_string()));
+    }
+
+    #[test]
+    fn explicit_int_repr_ignores_other_modifiers() {
+        let attrs: Vec<Attribute> = vec![parse_quote!(#[repr(u32, align(4))])];
+        assert_eq!(explicit_int_repr(&attrs), Some("u32".to_string()));
+    }
+
+    #[test]
+    fn explicit_int_repr_absent() {
+        let attrs: Vec<Attribute> = vec![parse_quote!(#[derive(Debug)])];
+        assert_eq!(explicit_int_repr(&attrs), None);
+    }
+
+    #[test]
+    fn enum_discriminants_default_and_explicit() {
+        let input: DeriveInput = parse_quote! {
+            enum Kind { A, B = 5, C }
+        };
+        let data = match input.data {
+            Data::Enum(data) => data,
+            _ => unreachable!(),
+        };
+        assert_eq!(enum_discriminants(&data), vec![0, 5, 6]);
+    }
+
+    #[test]
+    fn default_int_repr_picks_unsigned_when_possible() {
+        assert_eq!(default_int_repr(0, 10), "u32");
+        assert_eq!(default_int_repr(-1, 10), "i32");
+    }
+
+    #[test]
+    #[should_panic(expected = "do not fit")]
+    fn check_discriminants_fit_rejects_out_of_range() {
+        check_discriminants_fit("u32", -1, 10);
+    }
+
+    #[test]
+    fn has_runtime_sized_attr_detects_marker() {
+        let field: syn::Field = syn::Field::parse_named
+            .parse2(quote!(#[crevice(runtime_sized)] items: Vec<u32>))
+            .unwrap();
+        assert!(has_runtime_sized_attr(&field));
+    }
+
+    #[test]
+    fn has_runtime_sized_attr_false_without_marker() {
+        let field: syn::Field = syn::Field::parse_named
+            .parse2(quote!(items: Vec<u32>))
+            .unwrap();
+        assert!(!has_runtime_sized_attr(&field));
+    }
+
+    #[test]
+    fn runtime_sized_elem_ty_extracts_vec_element() {
+        let ty: syn::Type = parse_quote!(Vec<u32>);
+        let ty = runtime_sized_elem_ty(&ty);
+        assert_eq!(quote!(#ty).to_string(), quote!(u32).to_string());
+    }
+
+    #[test]
+    fn runtime_sized_elem_ty_extracts_slice_element() {
+        let ty: syn::Type = parse_quote!([u32]);
+        let ty = runtime_sized_elem_ty(&ty);
+        assert_eq!(quote!(#ty).to_string(), quote!(u32).to_string());
+    }
+
+    #[test]
+    fn explicit_align_attr_reads_override() {
+        let field: syn::Field = syn::Field::parse_named
+            .parse2(quote!(#[crevice(align = 16)] b: u32))
+            .unwrap();
+        assert_eq!(explicit_align_attr(&field), Some(16));
+    }
+
+    #[test]
+    fn explicit_align_attr_absent() {
+        let field: syn::Field = syn::Field::parse_named.parse2(quote!(b: u32)).unwrap();
+        assert_eq!(explicit_align_attr(&field), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "power of two")]
+    fn explicit_align_attr_rejects_non_power_of_two() {
+        let field: syn::Field = syn::Field::parse_named
+            .parse2(quote!(#[crevice(align = 3)] b: u32))
+            .unwrap();
+        explicit_align_attr(&field);
+    }
+
+    #[test]
+    fn validate_struct_attr_parses_field_offset_pairs() {
+        let attrs: Vec<Attribute> = vec![parse_quote!(#[crevice(validate(a = 0, b = 16))])];
+        assert_eq!(
+            validate_struct_attr(&attrs),
+            vec![("a".to_string(), 0), ("b".to_string(), 16)],
+        );
+    }
+
+    #[test]
+    fn validate_struct_attr_absent() {
+        let attrs: Vec<Attribute> = vec![parse_quote!(#[derive(Debug)])];
+        assert_eq!(validate_struct_attr(&attrs), Vec::new());
+    }
+}
@@ -0,0 +1,39 @@
+/// Per-field offset/size/alignment introspection metadata for a single field
+/// of a struct generated by `#[derive(AsStd140)]`/`#[derive(AsStd430)]`, as
+/// exposed through that struct's generated `FIELDS` const.
+///
+/// Lets callers cross-check the derive's generated layout against
+/// shader-reflection output without hand-computing offsets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldLayout {
+    /// The field's name, as written in the source struct.
+    pub name: &'static str,
+
+    /// The field's byte offset within the generated struct.
+    pub offset: usize,
+
+    /// The size in bytes of the field's layout-specific representation.
+    pub size: usize,
+
+    /// The field's alignment, including any `#[crevice(align = N)]` override.
+    pub alignment: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FieldLayout;
+
+    #[test]
+    fn equality_is_structural() {
+        let a = FieldLayout {
+            name: "a",
+            offset: 0,
+            size: 4,
+            alignment: 4,
+        };
+        let b = a;
+
+        assert_eq!(a, b);
+        assert_eq!(a.name, "a");
+    }
+}